@@ -11,13 +11,221 @@ use std::{
 use tracing_core::{span, Metadata};
 
 /// A single filtering directive.
-// TODO(eliza): add a builder for programmatically constructing directives?
 #[derive(Debug, Eq, PartialEq)]
 pub struct Directive {
+    target: Option<TargetPattern>,
+    in_span: Option<NamePattern>,
+    fields: FilterVec<field::Match>,
+    level: LevelFilter,
+    negate: bool,
+}
+
+/// A compiled target-matching pattern.
+///
+/// If the original filter string contains no `*`/`**` wildcards, matching
+/// falls back to the same `starts_with` prefix check used before glob
+/// support existed, so the common case has no added cost. Otherwise, the
+/// pattern is matched one `::`-separated segment at a time: `*` matches
+/// within a single segment (e.g. `db*` matches `db_conn`), and `**` spans
+/// any number of segments (including none).
+#[derive(Debug, Clone)]
+struct TargetPattern {
+    raw: String,
+    segments: Option<FilterVec<PathSegment>>,
+}
+
+/// A compiled span-name-matching pattern.
+///
+/// Like [`TargetPattern`], falls back to exact string equality when the
+/// pattern has no wildcards.
+#[derive(Debug, Clone)]
+struct NamePattern {
+    raw: String,
+    has_wildcard: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum PathSegment {
+    /// `**`: matches any number of `::`-separated segments, including none.
+    GlobStar,
+    /// A single segment, which may itself contain `*` wildcards.
+    Component(String),
+}
+
+impl TargetPattern {
+    fn new(raw: String) -> Self {
+        let segments = if raw.contains('*') {
+            Some(raw.split("::").map(PathSegment::new).collect())
+        } else {
+            None
+        };
+        Self { raw, segments }
+    }
+
+    fn matches(&self, target: &str) -> bool {
+        match &self.segments {
+            None => target.starts_with(&self.raw[..]),
+            Some(segments) => {
+                let target: FilterVec<&str> = target.split("::").collect();
+                segments_match(segments, &target)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// The number of wildcard segments in this pattern. Used to rank
+    /// directives by specificity: fewer wildcards is more specific.
+    fn wildcard_count(&self) -> usize {
+        self.segments
+            .as_ref()
+            .map(|segments| segments.iter().filter(|s| s.has_wildcard()).count())
+            .unwrap_or(0)
+    }
+}
+
+impl PartialEq for TargetPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for TargetPattern {}
+
+impl PartialOrd for TargetPattern {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TargetPattern {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl NamePattern {
+    fn new(raw: String) -> Self {
+        let has_wildcard = raw.contains('*');
+        Self { raw, has_wildcard }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        if self.has_wildcard {
+            segment_matches(&self.raw, name)
+        } else {
+            self.raw == name
+        }
+    }
+
+    /// Used to rank directives by specificity: fewer wildcards is more
+    /// specific. A span-name pattern is a single segment, so this is
+    /// either 0 or 1.
+    fn wildcard_count(&self) -> usize {
+        self.has_wildcard as usize
+    }
+
+    fn len(&self) -> usize {
+        self.raw.len()
+    }
+}
+
+impl PartialEq for NamePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for NamePattern {}
+
+impl PathSegment {
+    fn new(segment: &str) -> Self {
+        if segment == "**" {
+            PathSegment::GlobStar
+        } else {
+            PathSegment::Component(segment.to_owned())
+        }
+    }
+
+    fn has_wildcard(&self) -> bool {
+        match self {
+            PathSegment::GlobStar => true,
+            PathSegment::Component(s) => s.contains('*'),
+        }
+    }
+}
+
+/// Does `text` match the (possibly wildcarded) glob `pattern`, where `*`
+/// matches any run of characters?
+fn segment_matches(pattern: &str, text: &str) -> bool {
+    if !pattern.contains('*') {
+        return pattern == text;
+    }
+
+    let mut text = text;
+    let mut parts = pattern.split('*').peekable();
+    let mut first = true;
+    while let Some(part) = parts.next() {
+        if part.is_empty() {
+            first = false;
+            continue;
+        }
+        if first && parts.peek().is_some() {
+            match text.find(part) {
+                Some(0) => text = &text[part.len()..],
+                _ => return false,
+            }
+        } else if parts.peek().is_none() {
+            if !text.ends_with(part) {
+                return false;
+            }
+            text = &text[..text.len() - part.len()];
+        } else {
+            match text.find(part) {
+                Some(idx) => text = &text[idx + part.len()..],
+                None => return false,
+            }
+        }
+        first = false;
+    }
+    true
+}
+
+/// Matches a sequence of `::`-separated path segments (e.g. a target or
+/// recursively-split target) against a compiled pattern, honoring `**`
+/// segments that may span zero or more components.
+fn segments_match(pattern: &[PathSegment], text: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((PathSegment::GlobStar, rest)) => {
+            (0..=text.len()).any(|i| segments_match(rest, &text[i..]))
+        }
+        Some((PathSegment::Component(p), rest)) => match text.split_first() {
+            Some((first, tail)) if segment_matches(p, first) => segments_match(rest, tail),
+            _ => false,
+        },
+    }
+}
+
+/// Constructs a [`Directive`] programmatically, without parsing it from a
+/// filter string.
+///
+/// This is useful for embedders that already have a structured
+/// representation of their desired filtering configuration (for example,
+/// deserialized from a config file) and would otherwise have to format it
+/// into a string just to round-trip it through [`Directive`]'s [`FromStr`]
+/// implementation.
+///
+/// Constructed with [`Directive::builder`].
+#[derive(Debug, Default)]
+pub struct DirectiveBuilder {
     target: Option<String>,
     in_span: Option<String>,
     fields: FilterVec<field::Match>,
-    level: LevelFilter,
+    level: Option<LevelFilter>,
+    negate: bool,
 }
 
 /// A directive which will statically enable or disable a given callsite.
@@ -25,8 +233,9 @@ pub struct Directive {
 /// Unlike a dynamic directive, this can be cached by the callsite.
 #[derive(Debug, PartialEq, Eq, Ord)]
 pub struct StaticDirective {
-    target: Option<String>,
+    target: Option<TargetPattern>,
     level: LevelFilter,
+    negate: bool,
 }
 
 pub trait Match {
@@ -68,6 +277,12 @@ enum ParseErrorKind {
 }
 
 impl Directive {
+    /// Returns a builder for programmatically constructing a `Directive`,
+    /// without parsing it from a filter string.
+    pub fn builder() -> DirectiveBuilder {
+        DirectiveBuilder::default()
+    }
+
     pub(super) fn has_name(&self) -> bool {
         self.in_span.is_some()
     }
@@ -84,6 +299,7 @@ impl Directive {
         Ok(StaticDirective {
             target: self.target,
             level: self.level,
+            negate: self.negate,
         })
     }
 
@@ -114,9 +330,13 @@ impl Directive {
         Some(field::CallsiteMatch {
             fields,
             level: self.level.clone(),
+            negate: self.negate,
         })
     }
 
+    /// Converts a batch of [`Directive`]s (which may have been produced by
+    /// [`DirectiveBuilder::build`] as easily as by parsing) into a
+    /// [`Dynamics`] and [`Statics`] table.
     pub(super) fn make_tables(
         directives: impl IntoIterator<Item = Directive>,
     ) -> (Dynamics, Statics) {
@@ -131,16 +351,15 @@ impl Match for Directive {
     fn cares_about(&self, meta: &Metadata) -> bool {
         // Does this directive have a target filter, and does it match the
         // metadata's target?
-        if let Some(ref target) = self.target.as_ref() {
-            if !meta.target().starts_with(&target[..]) {
+        if let Some(ref target) = self.target {
+            if !target.matches(meta.target()) {
                 return false;
             }
         }
 
         // Do we have a name filter, and does it match the metadata's name?
-        // TODO(eliza): put name globbing here?
         if let Some(ref name) = self.in_span {
-            if name != meta.name() {
+            if !name.matches(meta.name()) {
                 return false;
             }
         }
@@ -182,20 +401,36 @@ impl FromStr for Directive {
             static ref SPAN_PART_RE: Regex =
                 Regex::new(r#"(?P<name>\w+)?(?:\{(?P<fields>[^\}]*)\})?"#).unwrap();
             static ref FIELD_FILTER_RE: Regex =
-                // TODO(eliza): this doesn't _currently_ handle value matchers that include comma
-                // characters. We should fix that.
                 Regex::new(r#"(?x)
                     (
                         # field name
                         [[:word:]][[[:word:]]\.]*
-                        # value part (optional)
-                        (?:=[^,]+)?
+                        # value part (optional): a comparison operator
+                        # followed by either a quoted string (which may
+                        # itself contain commas) or a bare token that ends
+                        # at the next comma.
+                        (?:
+                            \s*(?:==|!=|<=|>=|<|>|=)\s*
+                            (?:
+                                "(?:[^"\\]|\\.)*"
+                                |
+                                [^,]+
+                            )
+                        )?
                     )
                     # trailing comma or EOS
                     (?:,\s?|$)
                 "#).unwrap();
         }
 
+        // A leading `-` negates the directive: it turns matching callsites
+        // *off*, even if a less specific directive would otherwise enable
+        // them (see `Statics::enabled` and `Dynamics::matcher`).
+        let (negate, from) = match from.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, from),
+        };
+
         let caps = DIRECTIVE_RE.captures(from).ok_or_else(ParseError::new)?;
 
         if let Some(level) = caps
@@ -204,6 +439,7 @@ impl FromStr for Directive {
         {
             return Ok(Directive {
                 level,
+                negate,
                 ..Default::default()
             });
         }
@@ -213,7 +449,7 @@ impl FromStr for Directive {
             if s.parse::<LevelFilter>().is_ok() {
                 None
             } else {
-                Some(s.to_owned())
+                Some(TargetPattern::new(s.to_owned()))
             }
         });
 
@@ -222,13 +458,18 @@ impl FromStr for Directive {
             .and_then(|cap| {
                 let cap = cap.as_str().trim_matches(|c| c == '[' || c == ']');
                 let caps = SPAN_PART_RE.captures(cap)?;
-                let span = caps.name("name").map(|c| c.as_str().to_owned());
+                let span = caps.name("name").map(|c| NamePattern::new(c.as_str().to_owned()));
                 let fields = caps
                     .name("fields")
                     .map(|c| {
                         FIELD_FILTER_RE
-                            .find_iter(c.as_str())
-                            .map(|c| c.as_str().parse())
+                            .captures_iter(c.as_str())
+                            // Group 1 is the field name/value, without the
+                            // trailing separator matched by the rest of the
+                            // pattern; the whole match (`caps[0]`) would
+                            // include that separator and corrupt the parsed
+                            // value (see the regression test below).
+                            .map(|caps| caps.get(1).unwrap().as_str().parse())
                             .collect::<Result<FilterVec<_>, _>>()
                     })
                     .unwrap_or_else(|| Ok(FilterVec::new()));
@@ -246,6 +487,7 @@ impl FromStr for Directive {
             target,
             in_span,
             fields: fields?,
+            negate,
         })
     }
 }
@@ -257,6 +499,7 @@ impl Default for Directive {
             target: None,
             in_span: None,
             fields: FilterVec::new(),
+            negate: false,
         }
     }
 }
@@ -269,17 +512,44 @@ impl PartialOrd for Directive {
             _ => {}
         }
 
+        if let (Some(a), Some(b)) = (self.in_span.as_ref(), other.in_span.as_ref()) {
+            // Fewer wildcards is more specific, and ranks higher.
+            match a.wildcard_count().cmp(&b.wildcard_count()) {
+                Ordering::Equal => match a.len().cmp(&b.len()) {
+                    Ordering::Equal => {}
+                    ord => return Some(ord),
+                },
+                ord => return Some(ord.reverse()),
+            }
+        }
+
         match (self.fields.len(), other.fields.len()) {
             (a, b) if a == b => {}
             (a, b) => return Some(a.cmp(&b)),
         }
 
         match (self.target.as_ref(), other.target.as_ref()) {
-            (Some(a), Some(b)) => Some(a.len().cmp(&b.len())),
-            (Some(_), None) => Some(Ordering::Greater),
-            (None, Some(_)) => Some(Ordering::Less),
-            (None, None) => Some(Ordering::Equal),
+            (Some(a), Some(b)) => {
+                // Fewer wildcards is more specific, and ranks higher.
+                match a.wildcard_count().cmp(&b.wildcard_count()) {
+                    Ordering::Equal => match a.len().cmp(&b.len()) {
+                        Ordering::Equal => {}
+                        ord => return Some(ord),
+                    },
+                    ord => return Some(ord.reverse()),
+                }
+            }
+            (Some(_), None) => return Some(Ordering::Greater),
+            (None, Some(_)) => return Some(Ordering::Less),
+            (None, None) => {}
         }
+
+        // A negated directive and its otherwise-identical positive
+        // counterpart must still compare consistently so that the
+        // `BTreeSet` they're stored in retains a total order (and doesn't
+        // silently drop one of them as a duplicate). Treat the negation as
+        // the more specific of the two, since it exists to veto.
+        Some(self.negate.cmp(&other.negate))
     }
 }
 
@@ -290,6 +560,65 @@ impl Ord for Directive {
     }
 }
 
+// === impl DirectiveBuilder ===
+
+impl DirectiveBuilder {
+    /// Sets the target that this directive will match.
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Sets the span name that this directive will match.
+    pub fn in_span(mut self, name: impl Into<String>) -> Self {
+        self.in_span = Some(name.into());
+        self
+    }
+
+    /// Sets the level that this directive will enable.
+    ///
+    /// If this is not set, the directive defaults to [`LevelFilter::ERROR`],
+    /// matching the default used when a level is omitted from a parsed
+    /// directive string.
+    pub fn level(mut self, level: impl Into<LevelFilter>) -> Self {
+        self.level = Some(level.into());
+        self
+    }
+
+    /// Adds a field match that this directive requires.
+    ///
+    /// `field` is parsed the same way a field filter inside a directive
+    /// string's `{}` is parsed (e.g. `"count>10"` or `"user=\"bob\""`), so
+    /// it understands the same comparison operators as [`Directive`]'s
+    /// [`FromStr`] implementation. Like [`FromStr`], this can fail if
+    /// `field` isn't a valid field filter.
+    pub fn field(mut self, field: &str) -> Result<Self, ParseError> {
+        let field = field
+            .parse::<field::Match>()
+            .map_err(|e| ParseError::from(Box::new(e) as Box<dyn Error + Send + Sync>))?;
+        self.fields.push(field);
+        Ok(self)
+    }
+
+    /// Makes this a negated directive, which disables matching callsites
+    /// even when a less specific directive would otherwise enable them.
+    pub fn negate(mut self) -> Self {
+        self.negate = true;
+        self
+    }
+
+    /// Consumes this builder, returning the constructed [`Directive`].
+    pub fn build(self) -> Directive {
+        Directive {
+            target: self.target.map(TargetPattern::new),
+            in_span: self.in_span.map(NamePattern::new),
+            fields: self.fields,
+            level: self.level.unwrap_or(LevelFilter::ERROR),
+            negate: self.negate,
+        }
+    }
+}
+
 // === impl DirectiveSet ===
 
 impl<T> DirectiveSet<T> {
@@ -345,30 +674,48 @@ impl<T: Match + Ord> Extend<T> for DirectiveSet<T> {
 impl Dynamics {
     pub fn matcher(&self, metadata: &Metadata) -> Option<CallsiteMatcher> {
         let mut base_level = None;
-        let field_matches = self
-            .directives_for(metadata)
-            .filter_map(|d| {
+        let mut field_matches = FilterVec::new();
+        let mut negated = false;
+
+        // `directives_for` yields matching directives most-specific-first,
+        // so the first non-field directive we see wins the base level. A
+        // negation with no fields of its own vetoes unconditionally, so it
+        // stops us from considering anything less specific than it. A
+        // negation that carries its own fields (e.g. `-target[{user="bob"}]`)
+        // only vetoes the spans/events whose recorded fields actually
+        // satisfy it, which isn't known until the fields are recorded — so
+        // its `CallsiteMatch` is collected (tagged as negated) and we keep
+        // considering less specific directives as a fallback for the case
+        // where it doesn't end up matching.
+        for d in self.directives_for(metadata) {
+            if d.negate && !d.has_fields() {
+                negated = true;
+                break;
+            }
+            if d.has_fields() {
                 if let Some(f) = d.field_matcher(metadata) {
-                    return Some(f);
-                }
-                match base_level {
-                    Some(ref b) if &d.level > b => base_level = Some(d.level.clone()),
-                    None => base_level = Some(d.level.clone()),
-                    _ => {}
+                    field_matches.push(f);
+                    continue;
                 }
-                None
-            })
-            .collect();
+            }
+            if !d.negate && base_level.is_none() {
+                base_level = Some(d.level.clone());
+            }
+        }
 
         if let Some(base_level) = base_level {
             Some(CallsiteMatcher {
                 field_matches,
                 base_level,
             })
-        } else if !field_matches.is_empty() {
+        } else if !field_matches.is_empty() || negated {
+            // Either some field directive still applies, or the most
+            // specific match was a veto — in both cases we have an
+            // opinion (as opposed to "no directive matched at all"), so
+            // we must not return `None` here.
             Some(CallsiteMatcher {
                 field_matches,
-                base_level: base_level.unwrap_or(LevelFilter::OFF),
+                base_level: LevelFilter::OFF,
             })
         } else {
             None
@@ -382,7 +729,12 @@ impl Dynamics {
 impl Statics {
     pub fn enabled(&self, meta: &Metadata) -> bool {
         let level = meta.level();
-        self.directives_for(meta).any(|d| d.level >= *level)
+        // The most specific matching directive wins: if it's a negation,
+        // it vetoes the callsite regardless of what a less specific
+        // directive would have allowed.
+        self.directives_for(meta)
+            .next()
+            .map_or(false, |d| !d.negate && d.level >= *level)
     }
 
     pub fn add(&mut self, directive: StaticDirective) {
@@ -396,11 +748,25 @@ impl Statics {
 impl PartialOrd for StaticDirective {
     fn partial_cmp(&self, other: &StaticDirective) -> Option<Ordering> {
         match (self.target.as_ref(), other.target.as_ref()) {
-            (Some(a), Some(b)) => Some(a.len().cmp(&b.len())),
-            (Some(_), None) => Some(Ordering::Greater),
-            (None, Some(_)) => Some(Ordering::Less),
-            (None, None) => Some(Ordering::Equal),
+            (Some(a), Some(b)) => {
+                // Fewer wildcards is more specific, and ranks higher.
+                match a.wildcard_count().cmp(&b.wildcard_count()) {
+                    Ordering::Equal => match a.len().cmp(&b.len()) {
+                        Ordering::Equal => {}
+                        ord => return Some(ord),
+                    },
+                    ord => return Some(ord.reverse()),
+                }
+            }
+            (Some(_), None) => return Some(Ordering::Greater),
+            (None, Some(_)) => return Some(Ordering::Less),
+            (None, None) => {}
         }
+
+        // See the equivalent tiebreak in `Directive`'s `PartialOrd` impl:
+        // an otherwise-identical negation ranks as more specific, since it
+        // exists to veto.
+        Some(self.negate.cmp(&other.negate))
     }
 }
 
@@ -410,8 +776,8 @@ impl Match for StaticDirective {
     fn cares_about(&self, meta: &Metadata) -> bool {
         // Does this directive have a target filter, and does it match the
         // metadata's target?
-        if let Some(ref target) = self.target.as_ref() {
-            if !meta.target().starts_with(&target[..]) {
+        if let Some(ref target) = self.target {
+            if !target.matches(meta.target()) {
                 return false;
             }
         }
@@ -429,6 +795,7 @@ impl Default for StaticDirective {
         StaticDirective {
             target: None,
             level: LevelFilter::ERROR,
+            negate: false,
         }
     }
 }
@@ -506,9 +873,23 @@ impl CallsiteMatcher {
 
 impl SpanMatcher {
     /// Returns the level currently enabled for this callsite.
+    ///
+    /// A negated field match (e.g. from `-target[{user="bob"}]`) whose fields
+    /// are satisfied by the recorded values vetoes the span/event, taking
+    /// priority over any other matched field level. If it isn't satisfied,
+    /// it contributes nothing, and less specific directives still apply.
     pub fn level(&self) -> LevelFilter {
+        if self
+            .field_matches
+            .iter()
+            .any(|f| f.is_negate() && f.is_matched())
+        {
+            return LevelFilter::OFF;
+        }
+
         self.field_matches
             .iter()
+            .filter(|f| !f.is_negate())
             .filter_map(|f| {
                 if f.is_matched() {
                     Some(f.level())
@@ -526,3 +907,291 @@ impl SpanMatcher {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tracing_core::field::Visit;
+
+    #[test]
+    fn target_glob_star_matches_one_segment() {
+        let pattern = TargetPattern::new("my_app::*::db".to_owned());
+        assert!(pattern.matches("my_app::http::db"));
+        assert!(!pattern.matches("my_app::db"));
+        assert!(!pattern.matches("my_app::http::inner::db"));
+    }
+
+    #[test]
+    fn target_double_star_spans_any_number_of_segments() {
+        let pattern = TargetPattern::new("my_app::**::db".to_owned());
+        assert!(pattern.matches("my_app::db"));
+        assert!(pattern.matches("my_app::http::db"));
+        assert!(pattern.matches("my_app::http::inner::db"));
+        assert!(!pattern.matches("my_app::db::extra"));
+    }
+
+    #[test]
+    fn target_without_wildcard_still_prefix_matches() {
+        let pattern = TargetPattern::new("my_app".to_owned());
+        assert!(pattern.matches("my_app"));
+        assert!(pattern.matches("my_app::sub"));
+        assert!(!pattern.matches("other"));
+    }
+
+    #[test]
+    fn span_name_glob_matches_within_segment() {
+        let pattern = NamePattern::new("request_*".to_owned());
+        assert!(pattern.matches("request_1"));
+        assert!(pattern.matches("request_"));
+        assert!(!pattern.matches("other"));
+    }
+
+    #[test]
+    fn span_name_without_wildcard_is_exact() {
+        let pattern = NamePattern::new("request".to_owned());
+        assert!(pattern.matches("request"));
+        assert!(!pattern.matches("request_1"));
+    }
+
+    #[test]
+    fn exact_target_outranks_glob_target_of_equal_length() {
+        let exact: Directive = "my_app::http::db=trace".parse().unwrap();
+        let glob: Directive = "my_app::ht*p::db=debug".parse().unwrap();
+        assert_eq!(exact.cmp(&glob), Ordering::Greater);
+    }
+
+    #[test]
+    fn exact_span_name_outranks_glob_span_name() {
+        let exact: Directive = "[request_1]=trace".parse().unwrap();
+        let glob: Directive = "[request_*]=debug".parse().unwrap();
+        assert_eq!(exact.cmp(&glob), Ordering::Greater);
+    }
+
+    #[test]
+    fn mixed_specificity_directive_set_orders_most_specific_last() {
+        let mut directives: Vec<Directive> = vec![
+            "my_app=warn".parse().unwrap(),
+            "my_app::*::db=debug".parse().unwrap(),
+            "my_app::http::db=trace".parse().unwrap(),
+        ];
+        directives.sort();
+        // `directives_for` walks the set in reverse, so the most specific
+        // directive (the fully literal target) must sort last here.
+        assert_eq!(
+            directives.last().unwrap().target.as_ref().unwrap().raw,
+            "my_app::http::db"
+        );
+    }
+
+    #[test]
+    fn builder_round_trips_target_span_and_level() {
+        let directive = Directive::builder()
+            .target("my_app::http")
+            .in_span("request")
+            .level(LevelFilter::DEBUG)
+            .build();
+
+        assert_eq!(directive.target.unwrap().raw, "my_app::http");
+        assert_eq!(directive.in_span.unwrap().raw, "request");
+        assert_eq!(directive.level, LevelFilter::DEBUG);
+        assert!(!directive.negate);
+    }
+
+    #[test]
+    fn builder_defaults_level_to_error() {
+        let directive = Directive::builder().target("my_app").build();
+        assert_eq!(directive.level, LevelFilter::ERROR);
+    }
+
+    #[test]
+    fn builder_negate_sets_the_negate_flag() {
+        let directive = Directive::builder().target("my_app").negate().build();
+        assert!(directive.negate);
+    }
+
+    #[test]
+    fn builder_field_parses_a_field_filter() {
+        let directive = Directive::builder()
+            .target("my_app")
+            .field("count>10")
+            .unwrap()
+            .build();
+
+        assert_eq!(directive.fields.len(), 1);
+        assert_eq!(directive.fields[0].name, "count");
+        assert_eq!(
+            directive.fields[0].value,
+            Some(field::ValueMatch::I64(field::CompareOp::Gt, 10))
+        );
+    }
+
+    #[test]
+    fn builder_field_rejects_an_invalid_filter() {
+        assert!(Directive::builder().field("name>foo").is_err());
+    }
+
+    #[test]
+    fn multi_field_filter_parses_each_value_without_the_separator() {
+        let directive: Directive = "target[{a=1,b=2}]=debug".parse().unwrap();
+        assert_eq!(directive.fields.len(), 2);
+        assert_eq!(directive.fields[0].name, "a");
+        assert_eq!(
+            directive.fields[0].value,
+            Some(field::ValueMatch::I64(field::CompareOp::Eq, 1))
+        );
+        assert_eq!(directive.fields[1].name, "b");
+        assert_eq!(
+            directive.fields[1].value,
+            Some(field::ValueMatch::I64(field::CompareOp::Eq, 2))
+        );
+    }
+
+    #[test]
+    fn multi_field_filter_supports_relational_operators_on_non_last_field() {
+        let directive: Directive = "target[{count>10,name=foo}]=debug".parse().unwrap();
+        assert_eq!(directive.fields.len(), 2);
+        assert_eq!(
+            directive.fields[0].value,
+            Some(field::ValueMatch::I64(field::CompareOp::Gt, 10))
+        );
+    }
+
+    // A minimal, self-referential callsite used to build `Metadata` for the
+    // `Dynamics`/`Statics` precedence tests below, since this crate has no
+    // test-only dependency on `tracing`'s span/event macros.
+    struct TestCallsite {
+        metadata: Metadata<'static>,
+    }
+
+    impl tracing_core::Callsite for TestCallsite {
+        fn set_interest(&self, _interest: tracing_core::subscriber::Interest) {}
+        fn metadata(&self) -> &Metadata<'_> {
+            &self.metadata
+        }
+    }
+
+    static ADMIN_SPAN: TestCallsite = TestCallsite {
+        metadata: Metadata::new(
+            "admin",
+            "myapp",
+            tracing_core::Level::TRACE,
+            None,
+            None,
+            None,
+            tracing_core::field::FieldSet::new(
+                &[],
+                tracing_core::identify_callsite!(&ADMIN_SPAN),
+            ),
+            tracing_core::metadata::Kind::SPAN,
+        ),
+    };
+
+    #[test]
+    fn negation_outranks_less_specific_positive_directive() {
+        let broad: Directive = "myapp[admin]=trace".parse().unwrap();
+        let veto: Directive = "-myapp[admin]".parse().unwrap();
+        assert!(veto.negate);
+        assert_eq!(veto.cmp(&broad), Ordering::Greater);
+    }
+
+    #[test]
+    fn dynamics_matcher_vetoes_rather_than_abstaining() {
+        let dynamics = Dynamics::from_iter(vec![
+            "myapp[admin]=trace".parse::<Directive>().unwrap(),
+            "-myapp[admin]".parse::<Directive>().unwrap(),
+        ]);
+
+        let matcher = dynamics
+            .matcher(&ADMIN_SPAN.metadata)
+            .expect("a negation is a match, not an abstention, so this must be Some");
+        assert_eq!(matcher.base_level, LevelFilter::OFF);
+    }
+
+    struct UserFieldCallsite {
+        metadata: Metadata<'static>,
+    }
+
+    impl tracing_core::Callsite for UserFieldCallsite {
+        fn set_interest(&self, _interest: tracing_core::subscriber::Interest) {}
+        fn metadata(&self) -> &Metadata<'_> {
+            &self.metadata
+        }
+    }
+
+    static USER_SPAN: UserFieldCallsite = UserFieldCallsite {
+        metadata: Metadata::new(
+            "admin",
+            "myapp",
+            tracing_core::Level::TRACE,
+            None,
+            None,
+            None,
+            tracing_core::field::FieldSet::new(
+                &["user"],
+                tracing_core::identify_callsite!(&USER_SPAN),
+            ),
+            tracing_core::metadata::Kind::SPAN,
+        ),
+    };
+
+    #[test]
+    fn field_scoped_negation_only_vetoes_when_its_fields_match() {
+        // `-myapp[admin{user="bob"}]` should only veto spans where the
+        // recorded `user` field is actually `"bob"`, deferring to the less
+        // specific `myapp[admin]=trace` directive otherwise — unlike an
+        // unconditional veto, which would disable every `admin` span.
+        let dynamics = Dynamics::from_iter(vec![
+            "myapp[admin]=trace".parse::<Directive>().unwrap(),
+            Directive::builder()
+                .target("myapp")
+                .in_span("admin")
+                .field(r#"user="bob""#)
+                .unwrap()
+                .negate()
+                .build(),
+        ]);
+
+        let matcher = dynamics
+            .matcher(&USER_SPAN.metadata)
+            .expect("a field-scoped negation is still a match at the callsite level");
+        assert!(
+            matcher.field_matches.iter().any(|f| f.negate),
+            "the negated directive's field match should be collected, not just applied unconditionally"
+        );
+        let user_field = USER_SPAN.metadata.fields().field("user").unwrap();
+
+        // The recorded `user` field is `"bob"`: the negation's own field
+        // matches, so it should veto even though a less specific directive
+        // would otherwise enable this span at `trace`.
+        let bob_matches: FilterVec<_> = matcher
+            .field_matches
+            .iter()
+            .map(field::CallsiteMatch::to_span_match)
+            .collect();
+        for m in &bob_matches {
+            m.visitor().record_str(&user_field, "bob");
+        }
+        let bob_matcher = SpanMatcher {
+            field_matches: bob_matches,
+            base_level: matcher.base_level.clone(),
+        };
+        assert_eq!(bob_matcher.level(), LevelFilter::OFF);
+
+        // The recorded `user` field is `"alice"`: the negation's own field
+        // doesn't match, so it shouldn't veto, and the less specific
+        // `myapp[admin]=trace` directive should still apply.
+        let alice_matches: FilterVec<_> = matcher
+            .field_matches
+            .iter()
+            .map(field::CallsiteMatch::to_span_match)
+            .collect();
+        for m in &alice_matches {
+            m.visitor().record_str(&user_field, "alice");
+        }
+        let alice_matcher = SpanMatcher {
+            field_matches: alice_matches,
+            base_level: matcher.base_level,
+        };
+        assert_eq!(alice_matcher.level(), LevelFilter::TRACE);
+    }
+}
@@ -0,0 +1,446 @@
+use super::{level::LevelFilter, FieldMap};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    error::Error,
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tracing_core::field::{Field, Visit};
+
+/// Matches a field name and, optionally, a value that recorded values for
+/// that field must satisfy.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Match {
+    pub(crate) name: String,
+    pub(crate) value: Option<ValueMatch>,
+}
+
+/// The comparison used to test a recorded field value against a directive's
+/// bound.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A typed bound that a recorded field value is compared against.
+#[derive(Debug, Clone)]
+pub(crate) enum ValueMatch {
+    Bool(CompareOp, bool),
+    F64(CompareOp, f64),
+    I64(CompareOp, i64),
+    U64(CompareOp, u64),
+    /// A value that isn't a bool or a number, matched by its `Debug`
+    /// representation. Only `==`/`!=` are meaningful here; other operators
+    /// are rejected at parse time.
+    Debug(CompareOp, String),
+}
+
+impl PartialEq for ValueMatch {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ValueMatch::Bool(a, b), ValueMatch::Bool(c, d)) => a == c && b == d,
+            (ValueMatch::F64(a, b), ValueMatch::F64(c, d)) => a == c && b.to_bits() == d.to_bits(),
+            (ValueMatch::I64(a, b), ValueMatch::I64(c, d)) => a == c && b == d,
+            (ValueMatch::U64(a, b), ValueMatch::U64(c, d)) => a == c && b == d,
+            (ValueMatch::Debug(a, b), ValueMatch::Debug(c, d)) => a == c && b == d,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ValueMatch {}
+
+#[derive(Debug)]
+pub(crate) struct ParseMatchError {
+    message: String,
+}
+
+impl ParseMatchError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseMatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ParseMatchError {}
+
+impl FromStr for CompareOp {
+    type Err = ParseMatchError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "=" | "==" => Ok(CompareOp::Eq),
+            "!=" => Ok(CompareOp::Ne),
+            "<" => Ok(CompareOp::Lt),
+            "<=" => Ok(CompareOp::Le),
+            ">" => Ok(CompareOp::Gt),
+            ">=" => Ok(CompareOp::Ge),
+            _ => Err(ParseMatchError::new(format!(
+                "invalid comparison operator `{}`",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStr for Match {
+    type Err = ParseMatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref MATCH_RE: Regex = Regex::new(
+                r#"(?x)
+                    ^
+                    (?P<name>[[:word:]][[[:word:]]\.]*)
+                    \s*
+                    (?:
+                        (?P<op>==|!=|<=|>=|<|>|=)
+                        \s*
+                        (?P<value>
+                            "(?:[^"\\]|\\.)*"
+                            |
+                            \S+
+                        )
+                    )?
+                    $
+                "#
+            )
+            .unwrap();
+        }
+
+        let caps = MATCH_RE
+            .captures(s.trim())
+            .ok_or_else(|| ParseMatchError::new(format!("invalid field filter `{}`", s)))?;
+
+        let name = caps
+            .name("name")
+            .expect("a field name is required by MATCH_RE")
+            .as_str()
+            .to_owned();
+
+        let value = match (caps.name("op"), caps.name("value")) {
+            (Some(op), Some(value)) => {
+                let op = op.as_str().parse::<CompareOp>()?;
+                Some(ValueMatch::parse(op, value.as_str())?)
+            }
+            _ => None,
+        };
+
+        Ok(Match { name, value })
+    }
+}
+
+impl ValueMatch {
+    fn parse(op: CompareOp, raw: &str) -> Result<Self, ParseMatchError> {
+        if let Ok(value) = raw.parse::<bool>() {
+            return Ok(ValueMatch::Bool(op, value));
+        }
+        if let Ok(value) = raw.parse::<i64>() {
+            return Ok(ValueMatch::I64(op, value));
+        }
+        if let Ok(value) = raw.parse::<u64>() {
+            return Ok(ValueMatch::U64(op, value));
+        }
+        if let Ok(value) = raw.parse::<f64>() {
+            return Ok(ValueMatch::F64(op, value));
+        }
+
+        match op {
+            CompareOp::Eq | CompareOp::Ne => {
+                let unquoted = raw.trim_matches('"').to_owned();
+                Ok(ValueMatch::Debug(op, unquoted))
+            }
+            _ => Err(ParseMatchError::new(format!(
+                "`{:?}` only supports `=`/`!=` against a non-numeric, non-boolean value",
+                op
+            ))),
+        }
+    }
+
+    fn matches_bool(&self, recorded: bool) -> bool {
+        match self {
+            ValueMatch::Bool(op, bound) => compare(*op, *bound as u8 as f64, recorded as u8 as f64),
+            _ => false,
+        }
+    }
+
+    fn matches_f64(&self, recorded: f64) -> bool {
+        match self {
+            ValueMatch::F64(op, bound) => compare(*op, *bound, recorded),
+            ValueMatch::I64(op, bound) => compare(*op, *bound as f64, recorded),
+            ValueMatch::U64(op, bound) => compare(*op, *bound as f64, recorded),
+            _ => false,
+        }
+    }
+
+    fn matches_i64(&self, recorded: i64) -> bool {
+        match self {
+            ValueMatch::I64(op, bound) => compare(*op, *bound as f64, recorded as f64),
+            ValueMatch::U64(op, bound) => compare(*op, *bound as f64, recorded as f64),
+            ValueMatch::F64(op, bound) => compare(*op, *bound, recorded as f64),
+            _ => false,
+        }
+    }
+
+    fn matches_u64(&self, recorded: u64) -> bool {
+        match self {
+            ValueMatch::U64(op, bound) => compare(*op, *bound as f64, recorded as f64),
+            ValueMatch::I64(op, bound) => compare(*op, *bound as f64, recorded as f64),
+            ValueMatch::F64(op, bound) => compare(*op, *bound, recorded as f64),
+            _ => false,
+        }
+    }
+
+    fn matches_debug(&self, recorded: &str) -> bool {
+        match self {
+            ValueMatch::Debug(CompareOp::Eq, bound) => bound == recorded,
+            ValueMatch::Debug(CompareOp::Ne, bound) => bound != recorded,
+            _ => false,
+        }
+    }
+}
+
+fn compare(op: CompareOp, bound: f64, recorded: f64) -> bool {
+    match op {
+        CompareOp::Eq => (recorded - bound).abs() < f64::EPSILON,
+        CompareOp::Ne => (recorded - bound).abs() >= f64::EPSILON,
+        CompareOp::Lt => recorded < bound,
+        CompareOp::Le => recorded <= bound,
+        CompareOp::Gt => recorded > bound,
+        CompareOp::Ge => recorded >= bound,
+    }
+}
+
+/// A per-callsite set of field matchers, built once a dynamic directive is
+/// found to apply to a callsite (see `Directive::field_matcher`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CallsiteMatch {
+    pub(crate) fields: FieldMap<ValueMatch>,
+    pub(crate) level: LevelFilter,
+    /// Whether this match came from a negated directive. A negated match
+    /// that ends up satisfied vetoes the span/event (forces it off) rather
+    /// than contributing `level` as a candidate enabled level.
+    pub(crate) negate: bool,
+}
+
+impl CallsiteMatch {
+    /// Creates a per-span-instance match, which tracks whether each field's
+    /// comparison has actually been satisfied by a recorded value.
+    pub(crate) fn to_span_match(&self) -> SpanMatch {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(field, value)| (field.clone(), (value.clone(), AtomicBool::new(false))))
+            .collect();
+        SpanMatch {
+            fields,
+            base_level: self.level.clone(),
+            negate: self.negate,
+        }
+    }
+}
+
+/// Tracks, for a single span instance, whether its recorded fields satisfy
+/// a [`CallsiteMatch`]'s comparisons.
+#[derive(Debug)]
+pub(crate) struct SpanMatch {
+    fields: FieldMap<(ValueMatch, AtomicBool)>,
+    base_level: LevelFilter,
+    negate: bool,
+}
+
+impl PartialEq for SpanMatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.base_level == other.base_level
+            && self.negate == other.negate
+            && self.fields.len() == other.fields.len()
+            && self.fields.iter().all(|(field, (value, _))| {
+                other
+                    .fields
+                    .get(field)
+                    .map_or(false, |(other_value, _)| value == other_value)
+            })
+    }
+}
+
+impl Eq for SpanMatch {}
+
+impl SpanMatch {
+    pub(crate) fn visitor(&self) -> MatchVisitor<'_> {
+        MatchVisitor { match_: self }
+    }
+
+    /// Have all of this span's field comparisons been satisfied by a
+    /// recorded value?
+    pub(crate) fn is_matched(&self) -> bool {
+        self.fields
+            .values()
+            .all(|(_, matched)| matched.load(Ordering::Relaxed))
+    }
+
+    /// Whether this match came from a negated directive (see
+    /// [`CallsiteMatch::negate`]).
+    pub(crate) fn is_negate(&self) -> bool {
+        self.negate
+    }
+
+    pub(crate) fn level(&self) -> LevelFilter {
+        self.base_level.clone()
+    }
+}
+
+/// Visits a span or event's recorded fields, marking each [`SpanMatch`]
+/// field whose comparison is satisfied by the recorded value.
+pub(crate) struct MatchVisitor<'a> {
+    match_: &'a SpanMatch,
+}
+
+impl<'a> MatchVisitor<'a> {
+    fn record(&self, field: &Field, matches: impl FnOnce(&ValueMatch) -> bool) {
+        if let Some((value, matched)) = self.match_.fields.get(field) {
+            if matches(value) {
+                matched.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<'a> Visit for MatchVisitor<'a> {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, |m| m.matches_bool(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, |m| m.matches_i64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, |m| m.matches_u64(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, |m| m.matches_f64(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, |m| m.matches_debug(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, |m| m.matches_debug(&format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn match_without_value_is_name_only() {
+        let m: Match = "user".parse().unwrap();
+        assert_eq!(m.name, "user");
+        assert_eq!(m.value, None);
+    }
+
+    #[test]
+    fn match_parses_each_numeric_operator() {
+        let m: Match = "count>=10".parse().unwrap();
+        assert_eq!(m.name, "count");
+        assert_eq!(m.value, Some(ValueMatch::I64(CompareOp::Ge, 10)));
+
+        let m: Match = "count<=10".parse().unwrap();
+        assert_eq!(m.value, Some(ValueMatch::I64(CompareOp::Le, 10)));
+
+        let m: Match = "count!=10".parse().unwrap();
+        assert_eq!(m.value, Some(ValueMatch::I64(CompareOp::Ne, 10)));
+    }
+
+    #[test]
+    fn match_parses_bool_before_number() {
+        let m: Match = "enabled=true".parse().unwrap();
+        assert_eq!(m.value, Some(ValueMatch::Bool(CompareOp::Eq, true)));
+    }
+
+    #[test]
+    fn match_parses_float() {
+        let m: Match = "ratio>0.5".parse().unwrap();
+        assert_eq!(m.value, Some(ValueMatch::F64(CompareOp::Gt, 0.5)));
+    }
+
+    #[test]
+    fn match_parses_quoted_debug_value_with_comma() {
+        let m: Match = r#"name="bob, the builder""#.parse().unwrap();
+        assert_eq!(
+            m.value,
+            Some(ValueMatch::Debug(CompareOp::Eq, "bob, the builder".to_owned()))
+        );
+    }
+
+    #[test]
+    fn relational_operator_rejects_non_numeric_value() {
+        assert!("name>foo".parse::<Match>().is_err());
+    }
+
+    #[test]
+    fn value_match_compares_mixed_integer_types() {
+        assert!(ValueMatch::I64(CompareOp::Eq, 10).matches_u64(10));
+        assert!(ValueMatch::U64(CompareOp::Lt, 10).matches_i64(5));
+        assert!(ValueMatch::F64(CompareOp::Ge, 1.0).matches_i64(1));
+    }
+
+    #[test]
+    fn value_match_float_equality_uses_epsilon() {
+        assert!(ValueMatch::F64(CompareOp::Eq, 0.1 + 0.2).matches_f64(0.3));
+    }
+
+    #[test]
+    fn span_match_is_matched_once_every_field_is_recorded() {
+        struct TestCallsite;
+        impl tracing_core::Callsite for TestCallsite {
+            fn set_interest(&self, _interest: tracing_core::subscriber::Interest) {}
+            fn metadata(&self) -> &tracing_core::Metadata<'_> {
+                unreachable!("not needed for this test")
+            }
+        }
+        static CALLSITE: TestCallsite = TestCallsite;
+        let fields = tracing_core::field::FieldSet::new(
+            &["user", "count"],
+            tracing_core::identify_callsite!(&CALLSITE),
+        );
+        let user_field = fields.field("user").unwrap();
+        let count_field = fields.field("count").unwrap();
+
+        let callsite_match = CallsiteMatch {
+            fields: vec![
+                (user_field.clone(), ValueMatch::Debug(CompareOp::Eq, "bob".to_owned())),
+                (count_field.clone(), ValueMatch::I64(CompareOp::Gt, 10)),
+            ]
+            .into_iter()
+            .collect(),
+            level: LevelFilter::DEBUG,
+            negate: false,
+        };
+        let span_match = callsite_match.to_span_match();
+        assert!(!span_match.is_matched());
+
+        let mut visitor = span_match.visitor();
+        visitor.record_str(&user_field, "bob");
+        assert!(!span_match.is_matched());
+
+        visitor.record_i64(&count_field, 20);
+        assert!(span_match.is_matched());
+    }
+}